@@ -1,18 +1,28 @@
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
 
 use axum::{
-    body::Bytes,
-    extract::State,
-    http::{self, HeaderMap, HeaderValue, StatusCode},
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::{self, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use bytes::BytesMut;
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
 use tokio::{fs, sync::RwLock, time};
 use tower::ServiceBuilder;
-use tower_http::{compression::CompressionLayer, trace::TraceLayer};
+use tower_http::{
+    compression::{predicate::DefaultPredicate, CompressionLayer, Predicate},
+    trace::TraceLayer,
+};
 use tracing::{error, info};
 
 // Embedded fallback for /local/people when local file is missing
@@ -24,7 +34,81 @@ static EMBEDDED_EXAMPLE: &[u8] = include_bytes!("../assets/example.json");
 struct AppState {
     local_cache: Cached,
     remote_url: String,
-    remote_cache: Arc<RwLock<Option<Cached>>>,
+    remote_cache: Arc<dyn Cache>,
+    refresh_status: Arc<RwLock<RefreshStatus>>,
+    /// Bearer token sent on the outgoing upstream fetch, if configured.
+    remote_auth: Option<String>,
+    /// Gate applied to the inbound people endpoints.
+    auth: Arc<dyn ApiAuth>,
+    /// Consecutive refresh failures after which `/healthz` reports degraded.
+    failure_threshold: u32,
+}
+
+/// Authorization strategy for inbound requests. Mirrors the pluggable
+/// auth hook used elsewhere: the handler asks before serving.
+trait ApiAuth: Send + Sync {
+    /// Allow the request to proceed, or return the response to send instead.
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), Response>;
+}
+
+/// Default: every request is allowed.
+struct NoAuth;
+
+impl ApiAuth for NoAuth {
+    fn authorize(&self, _headers: &HeaderMap) -> Result<(), Response> {
+        Ok(())
+    }
+}
+
+/// Requires a shared secret presented as `Authorization: Bearer <token>`
+/// (the `token <token>` form is also accepted).
+struct StaticToken {
+    token: String,
+}
+
+impl ApiAuth for StaticToken {
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), Response> {
+        let provided = headers
+            .get(http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer ").or_else(|| v.strip_prefix("token ")));
+        // Compare SHA-256 digests in constant time: equal length avoids leaking
+        // the token's length and `ct_eq` avoids leaking it byte-by-byte.
+        let ok = provided.is_some_and(|t| {
+            Sha256::digest(t.as_bytes()).ct_eq(&Sha256::digest(self.token.as_bytes())).into()
+        });
+        if ok {
+            Ok(())
+        } else {
+            let mut h = HeaderMap::new();
+            h.insert(http::header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            Err((StatusCode::UNAUTHORIZED, h, "unauthorized").into_response())
+        }
+    }
+}
+
+/// Bounds for how often the background refresher revalidates the upstream.
+#[derive(Clone, Copy)]
+struct RefreshPolicy {
+    /// Lifetime used when upstream advertises no freshness information.
+    default: Duration,
+    /// Shortest allowed gap between refreshes.
+    floor: Duration,
+    /// Longest allowed gap between refreshes.
+    ceiling: Duration,
+}
+
+/// Live view of the refresher's schedule, shared for reporting.
+#[derive(Clone, Default)]
+struct RefreshStatus {
+    /// Freshness lifetime last derived from the upstream response.
+    lifetime: Option<Duration>,
+    /// When the next refresh is currently scheduled to fire. Consumed by the
+    /// planned `/debug` endpoint; kept written in the meantime.
+    #[allow(dead_code)]
+    next_refresh: Option<Instant>,
+    /// Number of `refresh_once` failures since the last success.
+    consecutive_failures: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -33,6 +117,80 @@ struct Cached {
     etag: String,
 }
 
+/// A keyed store for the remote payload and its ETag. Implementations decide
+/// whether the entry survives a restart.
+#[async_trait::async_trait]
+trait Cache: Send + Sync {
+    async fn get(&self, key: &str) -> Option<Cached>;
+    async fn put(&self, key: &str, value: Cached);
+}
+
+/// Process-lifetime cache wrapping the original `RwLock<Option<Cached>>` slot.
+struct InMemoryCache {
+    inner: RwLock<Option<Cached>>,
+}
+
+#[async_trait::async_trait]
+impl Cache for InMemoryCache {
+    async fn get(&self, _key: &str) -> Option<Cached> {
+        self.inner.read().await.clone()
+    }
+
+    async fn put(&self, _key: &str, value: Cached) {
+        *self.inner.write().await = Some(value);
+    }
+}
+
+/// Persistent cache that mirrors each entry to a file under `dir` named by the
+/// SHA-256 of its key (the name does not depend on the payload), keeping a hot
+/// copy in memory to avoid re-reading on every request. The hot copy is a
+/// single slot holding the entry last read or written, and `get` returns it
+/// whatever key is asked for.
+struct DiskCache {
+    dir: PathBuf,
+    mem: RwLock<Option<Cached>>,
+}
+
+impl DiskCache {
+    fn path_for(&self, key: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        self.dir.join(format!("{}.cache", hex::encode(hasher.finalize())))
+    }
+}
+
+#[async_trait::async_trait]
+impl Cache for DiskCache {
+    async fn get(&self, key: &str) -> Option<Cached> {
+        if let Some(c) = self.mem.read().await.clone() {
+            return Some(c);
+        }
+        // Entry layout: ETag on the first line, raw body after the newline.
+        let raw = fs::read(self.path_for(key)).await.ok()?;
+        let pos = raw.iter().position(|&b| b == b'\n')?;
+        let etag = String::from_utf8(raw[..pos].to_vec()).ok()?;
+        let bytes = Bytes::from(raw[pos + 1..].to_vec());
+        let cached = Cached { bytes, etag };
+        *self.mem.write().await = Some(cached.clone());
+        Some(cached)
+    }
+
+    async fn put(&self, key: &str, value: Cached) {
+        *self.mem.write().await = Some(value.clone());
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent).await;
+        }
+        let mut buf = Vec::with_capacity(value.etag.len() + 1 + value.bytes.len());
+        buf.extend_from_slice(value.etag.as_bytes());
+        buf.push(b'\n');
+        buf.extend_from_slice(&value.bytes);
+        if let Err(e) = fs::write(&path, buf).await {
+            error!(?e, ?path, "failed to persist remote cache to disk");
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     init_tracing();
@@ -43,21 +201,67 @@ async fn main() -> anyhow::Result<()> {
     // Default to reading from assets/people.json; can be overridden via LOCAL_PATH
     let local_path = std::env::var("LOCAL_PATH").unwrap_or_else(|_| "assets/people.json".to_string());
     let remote_url = std::env::var("REMOTE_URL").unwrap_or_else(|_| "https://raw.githubusercontent.com/cncf/people/refs/heads/main/people.json".to_string());
-    let refresh = std::env::var("REFRESH_INTERVAL").ok().and_then(|s| humantime::parse_duration(&s).ok()).unwrap_or_else(|| Duration::from_secs(600));
+    let parse_dur = |name: &str, default: Duration| {
+        std::env::var(name).ok().and_then(|s| humantime::parse_duration(&s).ok()).unwrap_or(default)
+    };
+    let floor = parse_dur("REFRESH_MIN_INTERVAL", Duration::from_secs(60));
+    let mut ceiling = parse_dur("REFRESH_MAX_INTERVAL", Duration::from_secs(3600));
+    if ceiling < floor {
+        error!(?floor, ?ceiling, "REFRESH_MAX_INTERVAL < REFRESH_MIN_INTERVAL; raising ceiling to floor");
+        ceiling = floor;
+    }
+    let policy = RefreshPolicy {
+        default: parse_dur("REFRESH_INTERVAL", Duration::from_secs(600)),
+        floor,
+        ceiling,
+    };
 
     let local_cache = load_local_cache(&local_path).await;
 
+    // A CACHE_DIR opts into the persistent on-disk backend; otherwise the cache
+    // lives only for the lifetime of the process.
+    let remote_cache: Arc<dyn Cache> = match std::env::var("CACHE_DIR") {
+        Ok(dir) if !dir.is_empty() => {
+            let cache = DiskCache { dir: PathBuf::from(dir), mem: RwLock::new(None) };
+            // Warm the hot copy from the last-known-good payload on disk so
+            // /people can serve real data before the first successful refresh.
+            if cache.get(&remote_url).await.is_some() {
+                info!("loaded last-known-good remote payload from disk");
+            }
+            Arc::new(cache)
+        }
+        _ => Arc::new(InMemoryCache { inner: RwLock::new(None) }),
+    };
+
+    let remote_auth = std::env::var("REMOTE_AUTH_TOKEN").ok().filter(|t| !t.is_empty());
+    let auth: Arc<dyn ApiAuth> = match std::env::var("API_AUTH_TOKEN") {
+        Ok(token) if !token.is_empty() => {
+            info!("inbound bearer-token auth enabled for people endpoints");
+            Arc::new(StaticToken { token })
+        }
+        _ => Arc::new(NoAuth),
+    };
+
     let state = AppState {
         local_cache,
         remote_url: remote_url.clone(),
-        remote_cache: Arc::new(RwLock::new(None)),
+        remote_cache,
+        refresh_status: Arc::new(RwLock::new(RefreshStatus::default())),
+        remote_auth,
+        auth,
+        failure_threshold: std::env::var("FAILURE_THRESHOLD")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3),
     };
 
+    let request_timeout = parse_dur("REQUEST_TIMEOUT", Duration::from_secs(30));
+
     // Kick off background refresher for remote cache
-    tokio::spawn(refresh_task(state.clone(), refresh));
+    tokio::spawn(refresh_task(state.clone(), policy));
 
     let app = Router::new()
-        .route("/healthz", get(|| async { (StatusCode::OK, "ok") }))
+        .route("/healthz", get(healthz))
         .route("/local/people", get(local_people))
         .route("/people", get(remote_people))
         .route("/example", get(example_json))
@@ -65,7 +269,11 @@ async fn main() -> anyhow::Result<()> {
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
-                .layer(CompressionLayer::new()),
+                // Leave ranged responses uncompressed so Content-Range/Length
+                // keep describing the raw bytes the client asked to resume from.
+                .layer(CompressionLayer::new().compress_when(DefaultPredicate::new().and(NotRanged)))
+                .layer(middleware::from_fn_with_state(CorsConfig::from_env(), cors))
+                .layer(middleware::from_fn_with_state(request_timeout, request_timeout_mw)),
         );
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -74,37 +282,163 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn local_people(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
-    let etag = state.local_cache.etag.clone();
+/// Bound how long a handler may take to *produce* a response, returning
+/// `408 Request Timeout` if it stalls. The deadline covers handler execution
+/// only, not the transport writing an already-buffered body to a slow client,
+/// so a legitimate bulk download of people.json is never aborted mid-stream.
+async fn request_timeout_mw(State(timeout): State<Duration>, req: Request, next: Next) -> Response {
+    match time::timeout(timeout, next.run(req)).await {
+        Ok(resp) => resp,
+        Err(_) => (StatusCode::REQUEST_TIMEOUT, "request timed out").into_response(),
+    }
+}
+
+async fn healthz(State(state): State<AppState>) -> Response {
+    let failures = state.refresh_status.read().await.consecutive_failures;
+    if failures >= state.failure_threshold {
+        let body = format!(
+            "{{\"status\":\"degraded\",\"detail\":\"remote stale, serving cached/embedded\",\"consecutive_failures\":{}}}",
+            failures
+        );
+        let mut h = HeaderMap::new();
+        h.insert("Content-Type", HeaderValue::from_static("application/json; charset=utf-8"));
+        return (StatusCode::SERVICE_UNAVAILABLE, h, body).into_response();
+    }
+    (StatusCode::OK, "ok").into_response()
+}
+
+async fn local_people(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(resp) = state.auth.authorize(&headers) {
+        return resp;
+    }
+    serve_cached(state.local_cache.bytes.clone(), &state.local_cache.etag, &headers)
+}
+
+async fn remote_people(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(resp) = state.auth.authorize(&headers) {
+        return resp;
+    }
+    if let Some(c) = state.remote_cache.get(&state.remote_url).await {
+        return serve_cached(c.bytes, &c.etag, &headers);
+    }
+    // Fallback to local if remote cache is empty
+    local_people(State(state), headers).await
+}
+
+/// Compression predicate that declines to compress `206 Partial Content` and
+/// any response carrying `Content-Range`, so the encoded body never diverges
+/// from the byte offsets advertised in `Content-Range`/`Content-Length`.
+#[derive(Clone, Copy)]
+struct NotRanged;
+
+impl Predicate for NotRanged {
+    fn should_compress<B>(&self, response: &http::Response<B>) -> bool
+    where
+        B: http_body::Body,
+    {
+        response.status() != StatusCode::PARTIAL_CONTENT
+            && !response.headers().contains_key(http::header::CONTENT_RANGE)
+    }
+}
+
+/// Outcome of parsing a `Range: bytes=...` header against a body of `len` bytes.
+#[derive(Debug, PartialEq, Eq)]
+enum RangeOutcome {
+    /// No range (or an unsupported form); serve the full body.
+    Full,
+    /// A satisfiable range, as inclusive byte offsets `[start, end]`.
+    Partial { start: u64, end: u64 },
+    /// The requested range lies entirely outside the body.
+    Unsatisfiable,
+}
+
+/// Parse a single-range `bytes=start-end` header, including the open-ended
+/// `bytes=start-` and suffix `bytes=-N` forms, clamping against `len`.
+fn parse_range(value: &str, len: u64) -> RangeOutcome {
+    let spec = match value.strip_prefix("bytes=") {
+        Some(s) => s.trim(),
+        None => return RangeOutcome::Full,
+    };
+    // Only a single range is supported; ignore multi-range requests.
+    if spec.contains(',') {
+        return RangeOutcome::Full;
+    }
+    let (a, b) = match spec.split_once('-') {
+        Some(p) => p,
+        None => return RangeOutcome::Full,
+    };
+    if len == 0 {
+        return RangeOutcome::Unsatisfiable;
+    }
+    let (start, end) = if a.is_empty() {
+        // Suffix form: bytes=-N serves the last N bytes.
+        let n: u64 = match b.parse() { Ok(n) => n, Err(_) => return RangeOutcome::Full };
+        if n == 0 { return RangeOutcome::Unsatisfiable; }
+        let n = n.min(len);
+        (len - n, len - 1)
+    } else {
+        let start: u64 = match a.parse() { Ok(n) => n, Err(_) => return RangeOutcome::Full };
+        let end = if b.is_empty() {
+            len - 1
+        } else {
+            match b.parse::<u64>() { Ok(n) => n.min(len - 1), Err(_) => return RangeOutcome::Full }
+        };
+        (start, end)
+    };
+    if start > end || start >= len {
+        return RangeOutcome::Unsatisfiable;
+    }
+    RangeOutcome::Partial { start, end }
+}
+
+/// Serve a cached payload, honoring conditional GET (`If-None-Match`) and
+/// `Range`/`If-Range` requests with `206 Partial Content` / `416` as needed.
+fn serve_cached(bytes: Bytes, etag: &str, headers: &HeaderMap) -> Response {
     if let Some(inm) = headers.get(http::header::IF_NONE_MATCH) {
         if let Ok(s) = inm.to_str() { if s == etag { return StatusCode::NOT_MODIFIED.into_response(); } }
     }
+    let len = bytes.len() as u64;
+
+    // A Range only applies when If-Range is absent or matches our ETag.
+    let range_applies = match headers.get(http::header::IF_RANGE) {
+        Some(v) => v.to_str().map(|s| s == etag).unwrap_or(false),
+        None => true,
+    };
 
     let mut h = HeaderMap::new();
     h.insert("Content-Type", HeaderValue::from_static("application/json; charset=utf-8"));
     h.insert("Cache-Control", HeaderValue::from_static("public, max-age=30"));
-    h.insert(http::header::ETAG, HeaderValue::from_str(&etag).unwrap());
-    (h, state.local_cache.bytes.clone()).into_response()
-}
+    h.insert(http::header::ETAG, HeaderValue::from_str(etag).unwrap());
+    h.insert(http::header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
 
-async fn remote_people(State(state): State<AppState>, headers: HeaderMap) -> Response {
-    if let Some(c) = state.remote_cache.read().await.clone() {
-        // Conditional GET support
-        if let Some(inm) = headers.get(http::header::IF_NONE_MATCH) {
-            if let Ok(s) = inm.to_str() { if s == c.etag { return StatusCode::NOT_MODIFIED.into_response(); } }
+    if range_applies {
+        if let Some(r) = headers.get(http::header::RANGE).and_then(|v| v.to_str().ok()) {
+            match parse_range(r, len) {
+                RangeOutcome::Partial { start, end } => {
+                    let slice = bytes.slice(start as usize..(end + 1) as usize);
+                    h.insert(
+                        http::header::CONTENT_RANGE,
+                        HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, len)).unwrap(),
+                    );
+                    return (StatusCode::PARTIAL_CONTENT, h, slice).into_response();
+                }
+                RangeOutcome::Unsatisfiable => {
+                    let mut eh = HeaderMap::new();
+                    eh.insert(http::header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+                    eh.insert(
+                        http::header::CONTENT_RANGE,
+                        HeaderValue::from_str(&format!("bytes */{}", len)).unwrap(),
+                    );
+                    return (StatusCode::RANGE_NOT_SATISFIABLE, eh).into_response();
+                }
+                RangeOutcome::Full => {}
+            }
         }
-        let mut h = HeaderMap::new();
-        h.insert("Content-Type", HeaderValue::from_static("application/json; charset=utf-8"));
-        h.insert("Cache-Control", HeaderValue::from_static("public, max-age=30"));
-        h.insert(http::header::ETAG, HeaderValue::from_str(&c.etag).unwrap());
-        return (h, c.bytes).into_response();
     }
-    // Fallback to local if remote cache is empty
-    let resp = local_people(State(state), headers).await;
-    resp.into_response()
+    (h, bytes).into_response()
 }
 
-async fn refresh_task(state: AppState, interval: Duration) {
+async fn refresh_task(state: AppState, policy: RefreshPolicy) {
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(5))
         .pool_max_idle_per_host(10)
@@ -112,39 +446,135 @@ async fn refresh_task(state: AppState, interval: Duration) {
         .build()
         .expect("client");
 
-    // Initial warm
-    if let Err(e) = refresh_once(&client, &state).await { error!(?e, "initial remote refresh failed"); }
-
-    let mut ticker = time::interval(interval);
+    // Initial warm; fall back to the floor if it fails so we retry promptly.
+    let mut next = run_refresh(&client, &state, &policy, true).await;
     loop {
-        ticker.tick().await;
-        if let Err(e) = refresh_once(&client, &state).await { error!(?e, "remote refresh failed"); }
+        time::sleep(next).await;
+        next = run_refresh(&client, &state, &policy, false).await;
+    }
+}
+
+/// Run one refresh, update the consecutive-failure counter, and return the
+/// delay until the next attempt.
+async fn run_refresh(
+    client: &reqwest::Client,
+    state: &AppState,
+    policy: &RefreshPolicy,
+    initial: bool,
+) -> Duration {
+    match refresh_once(client, state, policy).await {
+        Ok(d) => {
+            state.refresh_status.write().await.consecutive_failures = 0;
+            d
+        }
+        Err(e) => {
+            let mut st = state.refresh_status.write().await;
+            st.consecutive_failures = st.consecutive_failures.saturating_add(1);
+            if initial {
+                error!(?e, "initial remote refresh failed");
+            } else {
+                error!(?e, failures = st.consecutive_failures, "remote refresh failed");
+            }
+            policy.floor
+        }
     }
 }
 
-async fn refresh_once(client: &reqwest::Client, state: &AppState) -> anyhow::Result<()> {
-    let current_etag = state.remote_cache.read().await.as_ref().map(|c| c.etag.clone());
+async fn refresh_once(
+    client: &reqwest::Client,
+    state: &AppState,
+    policy: &RefreshPolicy,
+) -> anyhow::Result<Duration> {
+    let current_etag = state.remote_cache.get(&state.remote_url).await.map(|c| c.etag);
     let mut req = client.get(&state.remote_url);
     if let Some(et) = current_etag.as_ref() { req = req.header("If-None-Match", et); }
+    if let Some(token) = state.remote_auth.as_ref() { req = req.bearer_auth(token); }
     let resp = req.send().await?;
     match resp.status() {
         reqwest::StatusCode::OK => {
-            let etag = resp.headers().get("ETag").and_then(|v| v.to_str().ok()).map(|s| s.to_string());
+            let headers = resp.headers().clone();
+            let etag = headers.get("ETag").and_then(|v| v.to_str().ok()).map(|s| s.to_string());
             let bytes = resp.bytes().await?;
             let etag = etag.unwrap_or_else(|| strong_etag(&bytes));
             let cached = Cached { bytes, etag };
-            *state.remote_cache.write().await = Some(cached);
-            info!("remote cache refreshed");
+            state.remote_cache.put(&state.remote_url, cached).await;
+            let lifetime = freshness_lifetime(&headers, policy.floor)
+                .unwrap_or(policy.default)
+                .clamp(policy.floor, policy.ceiling);
+            schedule_next(state, lifetime).await;
+            info!(?lifetime, "remote cache refreshed");
+            Ok(lifetime)
         }
         reqwest::StatusCode::NOT_MODIFIED => {
-            // no-op
+            // Reuse the directives learned on the last full response.
+            let lifetime = state
+                .refresh_status
+                .read()
+                .await
+                .lifetime
+                .unwrap_or(policy.default)
+                .clamp(policy.floor, policy.ceiling);
+            schedule_next(state, lifetime).await;
             info!("remote not modified");
+            Ok(lifetime)
         }
         s => {
             anyhow::bail!("unexpected status: {}", s);
         }
     }
-    Ok(())
+}
+
+/// Record the chosen freshness lifetime and the resulting next-refresh instant.
+async fn schedule_next(state: &AppState, lifetime: Duration) {
+    let mut st = state.refresh_status.write().await;
+    st.lifetime = Some(lifetime);
+    st.next_refresh = Some(Instant::now() + lifetime);
+}
+
+/// Derive a freshness lifetime from a response's caching headers, following the
+/// usual `Cache-Control` precedence over `Expires`. `no-cache`/`no-store` pin
+/// revalidation to the floor; `None` means the upstream said nothing useful.
+fn freshness_lifetime(headers: &reqwest::header::HeaderMap, floor: Duration) -> Option<Duration> {
+    use reqwest::header::{AGE, CACHE_CONTROL, DATE, EXPIRES};
+
+    let cc = headers.get(CACHE_CONTROL).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let directives: Vec<String> = cc
+        .split(',')
+        .map(|d| d.trim().to_ascii_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect();
+
+    // Only no-cache/no-store pin to the floor. must-revalidate merely forbids
+    // serving stale after expiry — a no-op for a refresher that never serves
+    // knowingly-stale data — so it falls through to the max-age/Expires path.
+    if directives.iter().any(|d| d == "no-cache" || d == "no-store") {
+        return Some(floor);
+    }
+
+    // Discount the current Age against max-age, as a shared cache would.
+    let age = headers
+        .get(AGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    if let Some(max_age) = directives
+        .iter()
+        .find_map(|d| d.strip_prefix("max-age=").and_then(|v| v.parse::<u64>().ok()))
+    {
+        return Some(Duration::from_secs(max_age.saturating_sub(age)));
+    }
+
+    // Fall back to Expires relative to the response Date (or now).
+    if let Some(expires) = headers.get(EXPIRES).and_then(|v| v.to_str().ok()).and_then(|v| httpdate::parse_http_date(v).ok()) {
+        let base = headers
+            .get(DATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| httpdate::parse_http_date(v).ok())
+            .unwrap_or_else(SystemTime::now);
+        return Some(expires.duration_since(base).unwrap_or(floor));
+    }
+
+    None
 }
 
 fn strong_etag(b: &[u8]) -> String {
@@ -184,14 +614,164 @@ async fn load_local_cache(path: &str) -> Cached {
     }
 }
 
-async fn example_json(headers: HeaderMap) -> impl IntoResponse {
+async fn example_json(headers: HeaderMap) -> Response {
     let etag = strong_etag(EMBEDDED_EXAMPLE);
-    if let Some(inm) = headers.get(http::header::IF_NONE_MATCH) {
-        if let Ok(s) = inm.to_str() { if s == etag { return StatusCode::NOT_MODIFIED.into_response(); } }
+    serve_cached(Bytes::from_static(EMBEDDED_EXAMPLE), &etag, &headers)
+}
+
+/// Allow-list parsed from `CORS_ALLOW_ORIGINS` (comma-separated, or `*`).
+#[derive(Clone)]
+struct CorsConfig {
+    allow_any: bool,
+    origins: Vec<String>,
+}
+
+impl CorsConfig {
+    fn from_env() -> Self {
+        match std::env::var("CORS_ALLOW_ORIGINS") {
+            Ok(v) if v.trim() == "*" => Self { allow_any: true, origins: Vec::new() },
+            Ok(v) => Self {
+                allow_any: false,
+                origins: v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+            },
+            Err(_) => Self { allow_any: false, origins: Vec::new() },
+        }
+    }
+
+    fn allows(&self, origin: &str) -> bool {
+        self.allow_any || self.origins.iter().any(|o| o == origin)
+    }
+}
+
+/// Validate the request `Origin` against the allow-list, answer `OPTIONS`
+/// preflight with `204`, and decorate every response with the CORS headers.
+async fn cors(State(cfg): State<CorsConfig>, req: Request, next: Next) -> Response {
+    let origin = req.headers().get(http::header::ORIGIN).and_then(|v| v.to_str().ok()).map(str::to_owned);
+
+    if req.method() == Method::OPTIONS {
+        let mut resp = Response::new(Body::empty());
+        *resp.status_mut() = StatusCode::NO_CONTENT;
+        apply_cors_headers(&cfg, origin.as_deref(), resp.headers_mut());
+        return resp;
+    }
+
+    let mut resp = next.run(req).await;
+    apply_cors_headers(&cfg, origin.as_deref(), resp.headers_mut());
+    resp
+}
+
+fn apply_cors_headers(cfg: &CorsConfig, origin: Option<&str>, headers: &mut HeaderMap) {
+    // The response varies by Origin whether or not this particular one matches.
+    // Append rather than insert so any Vary dimension already on the response
+    // is preserved.
+    let has_origin = headers
+        .get_all(http::header::VARY)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.split(',').any(|d| d.trim().eq_ignore_ascii_case("origin")));
+    if !has_origin {
+        headers.append(http::header::VARY, HeaderValue::from_static("Origin"));
+    }
+    let Some(origin) = origin.filter(|o| cfg.allows(o)) else { return };
+
+    // Reflect a concrete origin; echo `*` only for a wildcard allow-list.
+    let allow = if cfg.allow_any { "*" } else { origin };
+    if let Ok(value) = HeaderValue::from_str(allow) {
+        headers.insert(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+    }
+    headers.insert(http::header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET, HEAD, OPTIONS"));
+    headers.insert(
+        http::header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("Authorization, If-None-Match, If-Range, Range"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_basic_and_open_ended() {
+        assert_eq!(parse_range("bytes=0-1023", 10_000), RangeOutcome::Partial { start: 0, end: 1023 });
+        assert_eq!(parse_range("bytes=500-", 1000), RangeOutcome::Partial { start: 500, end: 999 });
+        // End past the body is clamped to the last byte.
+        assert_eq!(parse_range("bytes=0-99999", 1000), RangeOutcome::Partial { start: 0, end: 999 });
+    }
+
+    #[test]
+    fn range_suffix_form() {
+        assert_eq!(parse_range("bytes=-100", 1000), RangeOutcome::Partial { start: 900, end: 999 });
+        // Suffix longer than the body yields the whole body.
+        assert_eq!(parse_range("bytes=-5000", 1000), RangeOutcome::Partial { start: 0, end: 999 });
+        // A zero-length suffix cannot be satisfied.
+        assert_eq!(parse_range("bytes=-0", 1000), RangeOutcome::Unsatisfiable);
+    }
+
+    #[test]
+    fn range_unsatisfiable_and_ignored() {
+        assert_eq!(parse_range("bytes=2000-3000", 1000), RangeOutcome::Unsatisfiable);
+        assert_eq!(parse_range("bytes=500-100", 1000), RangeOutcome::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-0", 0), RangeOutcome::Unsatisfiable);
+        // Multi-range and malformed specs fall back to the full body.
+        assert_eq!(parse_range("bytes=0-1,2-3", 1000), RangeOutcome::Full);
+        assert_eq!(parse_range("items=0-1", 1000), RangeOutcome::Full);
+    }
+
+    fn headers(pairs: &[(reqwest::header::HeaderName, &str)]) -> reqwest::header::HeaderMap {
+        let mut h = reqwest::header::HeaderMap::new();
+        for (name, value) in pairs {
+            h.insert(name.clone(), value.parse().unwrap());
+        }
+        h
+    }
+
+    #[test]
+    fn freshness_max_age_discounts_age() {
+        use reqwest::header::{AGE, CACHE_CONTROL};
+        let h = headers(&[(CACHE_CONTROL, "max-age=100"), (AGE, "40")]);
+        assert_eq!(freshness_lifetime(&h, Duration::from_secs(60)), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn freshness_no_cache_pins_to_floor() {
+        use reqwest::header::CACHE_CONTROL;
+        let floor = Duration::from_secs(30);
+        assert_eq!(freshness_lifetime(&headers(&[(CACHE_CONTROL, "no-cache")]), floor), Some(floor));
+        assert_eq!(freshness_lifetime(&headers(&[(CACHE_CONTROL, "no-store")]), floor), Some(floor));
+        // must-revalidate alone carries no freshness; it falls through to None.
+        assert_eq!(freshness_lifetime(&headers(&[(CACHE_CONTROL, "must-revalidate")]), floor), None);
+        // ...and does not shorten an accompanying max-age.
+        assert_eq!(
+            freshness_lifetime(&headers(&[(CACHE_CONTROL, "public, max-age=86400, must-revalidate")]), floor),
+            Some(Duration::from_secs(86400)),
+        );
+    }
+
+    #[test]
+    fn freshness_expires_relative_to_date() {
+        use reqwest::header::{DATE, EXPIRES};
+        let h = headers(&[
+            (DATE, "Mon, 01 Jan 2024 00:00:00 GMT"),
+            (EXPIRES, "Mon, 01 Jan 2024 00:10:00 GMT"),
+        ]);
+        assert_eq!(freshness_lifetime(&h, Duration::from_secs(60)), Some(Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn freshness_none_when_silent() {
+        assert_eq!(freshness_lifetime(&headers(&[]), Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn cors_allows_respects_list_and_wildcard() {
+        let any = CorsConfig { allow_any: true, origins: Vec::new() };
+        assert!(any.allows("https://anything.example"));
+
+        let listed = CorsConfig { allow_any: false, origins: vec!["https://app.example".into()] };
+        assert!(listed.allows("https://app.example"));
+        assert!(!listed.allows("https://evil.example"));
+
+        let empty = CorsConfig { allow_any: false, origins: Vec::new() };
+        assert!(!empty.allows("https://app.example"));
     }
-    let mut h = HeaderMap::new();
-    h.insert("Content-Type", HeaderValue::from_static("application/json; charset=utf-8"));
-    h.insert("Cache-Control", HeaderValue::from_static("public, max-age=30"));
-    h.insert(http::header::ETAG, HeaderValue::from_str(&etag).unwrap());
-    (h, Bytes::from_static(EMBEDDED_EXAMPLE)).into_response()
 }